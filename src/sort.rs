@@ -16,22 +16,75 @@ const MAX_INSERTION_SORT_ELEMS: usize = 42;
 /// Higher values give more insertion sorted elements.
 const INSERTION_SORT_FACTOR: usize = 450;
 
+/// The number of swaps a partial insertion sort performs before giving up.
+/// If a slice is sorted within this many swaps we are done; otherwise we bail
+/// out cheaply and fall back to quicksort.
+const PARTIAL_INSERTION_SORT_LIMIT: usize = 8;
+
 pub fn sort_by<T, C: Fn(&T, &T) -> Ordering>(v: &mut [T], compare: &C) {
     if maybe_insertion_sort(v, compare) { return; }
     let heapsort_depth = (3 * log2(v.len())) / 2;
-    do_introsort(v, compare, 0, heapsort_depth);
+    let bad_allowed = log2(v.len());
+    do_introsort(v, compare, 0, heapsort_depth, bad_allowed, None);
 }
 
 pub fn sort<T: Ord>(v: &mut [T]) {
     sort_by(v, &|a, b| a.cmp(b));
 }
 
-fn introsort<T, C: Fn(&T, &T) -> Ordering>(v: &mut [T], compare: &C, rec: u32, heapsort_depth: u32) {
+/// Sorts `v` by the key extracted by `f`. The key function is evaluated twice
+/// per comparison, so prefer `sort_by_cached_key` when it is expensive.
+pub fn sort_by_key<T, K: Ord, F: Fn(&T) -> K>(v: &mut [T], f: &F) {
+    sort_by(v, &|a, b| f(a).cmp(&f(b)));
+}
+
+/// Sorts `v` by the key extracted by `f`, without preserving the order of
+/// equal elements. This sort is already unstable; the name mirrors the standard
+/// library so it can be used as a drop-in replacement.
+pub fn sort_unstable_by_key<T, K: Ord, F: Fn(&T) -> K>(v: &mut [T], f: &F) {
+    sort_by(v, &|a, b| f(a).cmp(&f(b)));
+}
+
+/// Sorts `v` by the key extracted by `f`, evaluating `f` only once per element.
+///
+/// The keys are extracted into a temporary `Vec<(K, usize)>` paired with the
+/// original indices, that vector is sorted, and `v` is then permuted into place
+/// by following the resulting index chains with one swap per position; an
+/// element can be moved more than once before it reaches its final slot. This
+/// gives Schwartzian-transform performance for costly keys at the cost of the
+/// allocation.
+pub fn sort_by_cached_key<T, K: Ord, F: Fn(&T) -> K>(v: &mut [T], f: &F) {
+    let mut indices: Vec<(K, usize)> =
+        v.iter().enumerate().map(|(i, x)| (f(x), i)).collect();
+    sort_by(&mut indices, &|a, b| a.0.cmp(&b.0));
+    // Follow the chains of the permutation so each position is filled by one swap.
+    for i in 0..v.len() {
+        let mut index = indices[i].1;
+        while index < i {
+            index = indices[index].1;
+        }
+        indices[i].1 = index;
+        v.swap(i, index);
+    }
+}
+
+fn introsort<T, C: Fn(&T, &T) -> Ordering>(v: &mut [T], compare: &C, rec: u32, heapsort_depth: u32, bad_allowed: u32, pred: Option<&T>) {
     if maybe_insertion_sort(v, compare) { return; }
-    do_introsort(v, compare, rec, heapsort_depth);
+    do_introsort(v, compare, rec, heapsort_depth, bad_allowed, pred);
 }
 
-fn do_introsort<T, C: Fn(&T, &T) -> Ordering>(v: &mut [T], compare: &C, rec: u32, heapsort_depth: u32) {
+/// The result of the pivot-selection step: either five candidates for a
+/// dual-pivot partition, or a single pivot when there are probably many
+/// similar elements.
+enum PivotChoice {
+    Dual((usize, usize, usize, usize, usize)),
+    Single(usize),
+}
+
+/// Runs the five-element sorting network and decides between the dual-pivot and
+/// single-pivot strategies. Returns the median index (used to detect runs of
+/// keys equal to the predecessor) alongside the choice.
+fn choose_pivots<T, C: Fn(&T, &T) -> Ordering>(v: &mut [T], compare: &C) -> (usize, PivotChoice) {
     macro_rules! maybe_swap(
         ($v: expr, $a: expr, $b: expr, $compare: expr) => {
             if compare_idxs($v, *$a, *$b, $compare) == Greater {
@@ -40,11 +93,6 @@ fn do_introsort<T, C: Fn(&T, &T) -> Ordering>(v: &mut [T], compare: &C, rec: u32
         }
     );
 
-    if rec > heapsort_depth {
-        heapsort(v, compare);
-        return;
-    }
-
     let n = v.len();
 
     // Pivot selection algorithm based on Java's DualPivotQuicksort.
@@ -72,16 +120,94 @@ fn do_introsort<T, C: Fn(&T, &T) -> Ordering>(v: &mut [T], compare: &C, rec: u32
         maybe_swap!(v, &mut e2, &mut e3, compare);
     }
 
-    if unsafe { compare_idxs(v, e1, e2, compare) != Equal &&
-                compare_idxs(v, e2, e3, compare) != Equal &&
-                compare_idxs(v, e3, e4, compare) != Equal &&
-                compare_idxs(v, e4, e5, compare) != Equal } {
+    let choice = if unsafe { compare_idxs(v, e1, e2, compare) != Equal &&
+                             compare_idxs(v, e2, e3, compare) != Equal &&
+                             compare_idxs(v, e3, e4, compare) != Equal &&
+                             compare_idxs(v, e4, e5, compare) != Equal } {
         // No consecutive pivot candidates are the same, meaning there is some variaton.
-        dual_pivot_sort(v, (e1, e2, e3, e4, e5), compare, rec, heapsort_depth);
+        PivotChoice::Dual((e1, e2, e3, e4, e5))
     } else {
         // Two consecutive pivots candidates where the same.
         // There are probably many similar elements.
-        single_pivot_sort(v, e3, compare, rec, heapsort_depth);
+        PivotChoice::Single(e3)
+    };
+    (e3, choice)
+}
+
+fn do_introsort<T, C: Fn(&T, &T) -> Ordering>(v: &mut [T], compare: &C, rec: u32, heapsort_depth: u32, bad_allowed: u32, pred: Option<&T>) {
+    if rec > heapsort_depth {
+        heapsort(v, compare);
+        return;
+    }
+
+    // Exploit slices that are already (almost) sorted: an insertion sort that
+    // gives up after a few swaps sorts such inputs in linear time, and bails
+    // out cheaply otherwise.
+    if partial_insertion_sort(v, compare) {
+        return;
+    }
+
+    let n = v.len();
+    let (median, choice) = choose_pivots(v, compare);
+
+    // If this subslice sits immediately to the right of a block of elements all
+    // equal to `pred` and its median equals `pred` too, nearly everything here
+    // is a duplicate of the predecessor. Sweep the equal elements to the front
+    // in a single linear pass and recurse only on the strictly-greater tail.
+    if let Some(p) = pred {
+        if unsafe { compare(v.get_unchecked(median), p) } == Equal {
+            let eq = partition_equal(v, median, compare);
+            if n - eq > 1 {
+                introsort(&mut v[eq..], compare, rec + 1, heapsort_depth, bad_allowed, pred);
+            }
+            return;
+        }
+    }
+
+    match choice {
+        PivotChoice::Dual(pivots) => dual_pivot_sort(v, pivots, compare, rec, heapsort_depth, bad_allowed, pred),
+        PivotChoice::Single(pivot) => single_pivot_sort(v, pivot, compare, rec, heapsort_depth, bad_allowed, pred),
+    }
+}
+
+/// Sorts `v` with insertion sort, giving up after `PARTIAL_INSERTION_SORT_LIMIT`
+/// swaps. A strictly descending slice is reversed in place instead. Returns
+/// `true` if the slice is fully sorted, `false` if it bailed out.
+fn partial_insertion_sort<T, C: Fn(&T, &T) -> Ordering>(v: &mut [T], compare: &C) -> bool {
+    let n = v.len();
+    if n > 1 && (1..n).all(|i| unsafe { compare_idxs(v, i-1, i, compare) } == Greater) {
+        v.reverse();
+        return true;
+    }
+    let mut swaps = 0;
+    let mut i = 1;
+    while i < n {
+        let mut j = i;
+        while j > 0 && unsafe { compare_idxs(v, j-1, j, compare) } == Greater {
+            unsafe { unsafe_swap(v, j, j-1); }
+            j -= 1;
+            swaps += 1;
+            if swaps > PARTIAL_INSERTION_SORT_LIMIT {
+                return false;
+            }
+        }
+        i += 1;
+    }
+    true
+}
+
+/// Scrambles a handful of elements at fixed offsets. Used to break up the
+/// adversarial patterns that make a median-of-five pivot produce repeatedly
+/// imbalanced partitions.
+fn break_patterns<T>(v: &mut [T]) {
+    let n = v.len();
+    if n < 8 { return; }
+    let quarter = n / 4;
+    let mid = n / 2;
+    unsafe {
+        unsafe_swap(v, 0, quarter);
+        unsafe_swap(v, mid - 1, mid);
+        unsafe_swap(v, n - 1, n - 1 - quarter);
     }
 }
 
@@ -145,9 +271,12 @@ impl<'a, T: 'a> Drop for DualPivotSort<'a, T> {
     }
 }
 
-fn dual_pivot_sort<T, C: Fn(&T, &T) -> Ordering>(v: &mut [T], pivots: (usize, usize, usize, usize, usize),
-                                                 compare: &C, rec: u32, heapsort_depth: u32) {
-    let (less, great) = unsafe {
+/// Runs the dual-pivot partition step and returns the `(less, great)` boundary
+/// indices. The RAII guard restores the two pivots to their slots before
+/// returning, so the caller only has to sort the three disjoint parts.
+fn dual_pivot_partition<T, C: Fn(&T, &T) -> Ordering>(v: &mut [T], pivots: (usize, usize, usize, usize, usize),
+                                                      compare: &C) -> (usize, usize) {
+    unsafe {
         let n = v.len();
         let (_, p1, _, p2, _) = pivots;
 
@@ -203,22 +332,110 @@ fn dual_pivot_sort<T, C: Fn(&T, &T) -> Ordering>(v: &mut [T], pivots: (usize, us
 
         // The pivots are swapped back when this is dropped.
         (this.less, this.great)
+    }
+}
+
+fn dual_pivot_sort<T, C: Fn(&T, &T) -> Ordering>(v: &mut [T], pivots: (usize, usize, usize, usize, usize),
+                                                 compare: &C, rec: u32, heapsort_depth: u32, bad_allowed: u32, pred: Option<&T>) {
+    let (less, great) = dual_pivot_partition(v, pivots, compare);
+
+    // If one of the three parts dwarfs the others the pivots were a poor
+    // choice; break up the offending pattern and spend one unit of the
+    // `bad_allowed` budget, falling back to heapsort once it is exhausted.
+    let n = v.len();
+    let left = less - 1;
+    let center = great + 1 - less;
+    let right = n - (great + 2);
+    let bad_allowed = if min(left, min(center, right)) < n / 8 {
+        if bad_allowed == 0 {
+            heapsort(v, compare);
+            return;
+        }
+        break_patterns(&mut v[..less - 1]);
+        break_patterns(&mut v[great + 2..]);
+        bad_allowed - 1
+    } else {
+        bad_allowed
     };
 
-    // Sort the left, right, and center parts.
-    introsort(&mut v[..less - 1], compare, rec + 1, heapsort_depth);
-    introsort(&mut v[less..great + 1], compare, rec + 1, heapsort_depth);
-    introsort(&mut v[great + 2..], compare, rec + 1, heapsort_depth);
+    // Sort the left, right, and center parts. The restored pivots at `less - 1`
+    // and `great + 1` act as predecessors for the center and right parts, so a
+    // run of keys equal to a pivot can be partitioned away in one pass.
+    introsort(&mut v[..less - 1], compare, rec + 1, heapsort_depth, bad_allowed, pred);
+    {
+        let (head, tail) = v.split_at_mut(less);
+        introsort(&mut tail[..great + 1 - less], compare, rec + 1, heapsort_depth, bad_allowed, Some(&head[less - 1]));
+    }
+    {
+        let (head, tail) = v.split_at_mut(great + 2);
+        introsort(tail, compare, rec + 1, heapsort_depth, bad_allowed, Some(&head[great + 1]));
+    }
 }
 
-fn single_pivot_sort<T, C: Fn(&T, &T) -> Ordering>(v: &mut [T], pivot: usize, compare: &C, rec: u32, heapsort_depth: u32) {
-    let (l, r) = fat_partition(v, pivot, compare);
+fn single_pivot_sort<T, C: Fn(&T, &T) -> Ordering>(v: &mut [T], pivot: usize, compare: &C, rec: u32, heapsort_depth: u32, bad_allowed: u32, pred: Option<&T>) {
     let n = v.len();
+
+    // For small, cheaply-compared types the branchless block partitioner beats
+    // the branchy fat partition on random data. Larger types keep `fat_partition`,
+    // whose equal-element handling pays off in the many-duplicates case that
+    // routes here.
+    //
+    // Note that `block_partition` is only two-way and does *not* coalesce equal
+    // elements the way `fat_partition` does. We stay non-quadratic on inputs
+    // with O(1) distinct values because the greater-or-equal part is handed its
+    // pivot as `pred`, so `do_introsort` strips the next run of equal keys via
+    // `partition_equal` in a single linear pass (see chunk0-2). Without that
+    // predecessor fast path this route would degrade on many-equal inputs.
+    if size_of::<T>() <= 2 * size_of::<usize>() {
+        let p = block_partition(v, pivot, compare);
+        let l = p;
+        let r = n - p - 1;
+        let bad_allowed = if min(l, r) < n / 8 {
+            if bad_allowed == 0 {
+                heapsort(v, compare);
+                return;
+            }
+            if l > 1 { break_patterns(&mut v[..p]); }
+            if r > 1 { break_patterns(&mut v[p + 1..]); }
+            bad_allowed - 1
+        } else {
+            bad_allowed
+        };
+        if l > 1 {
+            introsort(&mut v[..p], compare, rec + 1, heapsort_depth, bad_allowed, pred);
+        }
+        if r > 1 {
+            // The pivot itself precedes the greater part and seeds the
+            // equal-key fast path for the next level of recursion.
+            let (head, greater) = v.split_at_mut(p + 1);
+            introsort(greater, compare, rec + 1, heapsort_depth, bad_allowed, Some(&head[p]));
+        }
+        return;
+    }
+
+    let (l, r) = fat_partition(v, pivot, compare);
+    // A wildly imbalanced partition signals an adversarial input; break the
+    // pattern and spend one unit of the `bad_allowed` budget, falling back to
+    // heapsort once it is exhausted.
+    let bad_allowed = if min(l, r) < n / 8 {
+        if bad_allowed == 0 {
+            heapsort(v, compare);
+            return;
+        }
+        if l > 1 { break_patterns(&mut v[..l]); }
+        if r > 1 { break_patterns(&mut v[n - r..]); }
+        bad_allowed - 1
+    } else {
+        bad_allowed
+    };
     if l > 1 {
-        introsort(&mut v[..l], compare, rec + 1, heapsort_depth);
+        introsort(&mut v[..l], compare, rec + 1, heapsort_depth, bad_allowed, pred);
     }
     if r > 1 {
-        introsort(&mut v[n - r..], compare, rec + 1, heapsort_depth);
+        // The strictly-greater part is preceded by the block of keys equal to
+        // the pivot; hand the last of those down as its predecessor.
+        let (head, greater) = v.split_at_mut(n - r);
+        introsort(greater, compare, rec + 1, heapsort_depth, bad_allowed, Some(&head[n - r - 1]));
     }
 }
 
@@ -266,6 +483,101 @@ fn fat_partition<T, C: Fn(&T, &T) -> Ordering>(v: &mut [T], pivot: usize, compar
     return (b - a, d - c);
 }
 
+/// The number of elements processed per block by `partition_in_blocks`.
+const BLOCK: usize = 128;
+
+/// Branchless block partition around `pivot`, modelled on pdqsort's
+/// `partition_in_blocks`. Returns the number of elements comparing `Less` than
+/// the pivot; on return those elements occupy the front of `v`. Instead of a
+/// hard-to-predict branch per element, each comparison result is folded into a
+/// data-independent store (`offsets[count] = i; count += misplaced as usize;`),
+/// and the collected out-of-order pairs are exchanged in a tight loop.
+fn partition_in_blocks<T, C: Fn(&T, &T) -> Ordering>(v: &mut [T], pivot: &T, compare: &C) -> usize {
+    let mut l = 0;
+    let mut r = v.len();
+    let mut offsets_l = [0u8; BLOCK];
+    let mut offsets_r = [0u8; BLOCK];
+    let mut start_l = 0; let mut num_l = 0;
+    let mut start_r = 0; let mut num_r = 0;
+
+    // While there is room for two non-overlapping blocks, scan a block from each
+    // end filling the offset buffers without branching, then swap the pairs.
+    while r - l > 2 * BLOCK {
+        if num_l == 0 {
+            start_l = 0;
+            let mut i = 0;
+            while i < BLOCK {
+                offsets_l[num_l] = i as u8;
+                num_l += (compare(&v[l + i], pivot) != Less) as usize;
+                i += 1;
+            }
+        }
+        if num_r == 0 {
+            start_r = 0;
+            let mut i = 0;
+            while i < BLOCK {
+                offsets_r[num_r] = i as u8;
+                num_r += (compare(&v[r - 1 - i], pivot) == Less) as usize;
+                i += 1;
+            }
+        }
+        let count = min(num_l - start_l, num_r - start_r);
+        for _ in 0..count {
+            let li = l + offsets_l[start_l] as usize;
+            let ri = r - 1 - offsets_r[start_r] as usize;
+            unsafe { unsafe_swap(v, li, ri); }
+            start_l += 1;
+            start_r += 1;
+        }
+        if start_l == num_l { l += BLOCK; num_l = 0; }
+        if start_r == num_r { r -= BLOCK; num_r = 0; }
+    }
+
+    // Partition whatever is left over with a plain branchy sweep.
+    while l < r {
+        while l < r && compare(&v[l], pivot) == Less { l += 1; }
+        while l < r && compare(&v[r - 1], pivot) != Less { r -= 1; }
+        if l < r {
+            r -= 1;
+            unsafe { unsafe_swap(v, l, r); }
+            l += 1;
+        }
+    }
+    l
+}
+
+/// Two-way partition around the element at `pivot` using `partition_in_blocks`.
+/// Returns the final resting index of the pivot: everything before it compares
+/// `Less`, everything after compares `Greater` or `Equal`.
+fn block_partition<T, C: Fn(&T, &T) -> Ordering>(v: &mut [T], pivot: usize, compare: &C) -> usize {
+    v.swap(0, pivot);
+    let m = {
+        let (first, tail) = v.split_at_mut(1);
+        partition_in_blocks(tail, &first[0], compare)
+    };
+    v.swap(0, m);
+    m
+}
+
+/// Partitions `v` around the element at `pivot`, which is assumed to be the
+/// smallest element of `v` (every element compares `Equal` or `Greater`).
+/// Moves all elements equal to the pivot to the front and the greater ones to
+/// the back, and returns the number of equal elements.
+fn partition_equal<T, C: Fn(&T, &T) -> Ordering>(v: &mut [T], pivot: usize, compare: &C) -> usize {
+    v.swap(0, pivot);
+    let mut l = 1;
+    let mut r = v.len();
+    loop {
+        while l < r && compare_idxs_safe(v, l, 0, compare) != Greater { l += 1; }
+        while l < r && compare_idxs_safe(v, r - 1, 0, compare) == Greater { r -= 1; }
+        if l >= r { break; }
+        r -= 1;
+        unsafe { unsafe_swap(v, l, r); }
+        l += 1;
+    }
+    l
+}
+
 unsafe fn swap_many<T>(v: &mut [T], a: usize, b: usize, n: usize) {
     let mut i = 0;
     while i < n {
@@ -419,3 +731,197 @@ fn compare_idxs_safe<T, C: Fn(&T, &T) -> Ordering>(v: &[T], a: usize, b: usize,
 unsafe fn unsafe_swap<T>(v: &mut[T], a: usize, b: usize) {
     ptr::swap(v.get_unchecked_mut(a) as *mut T, v.get_unchecked_mut(b) as *mut T);
 }
+
+/// Parallel sorting, gated behind the `rayon` feature.
+///
+/// The partitioning code is shared verbatim with the sequential sort; only the
+/// disjoint recursive subslice sorts are turned into work-stealing fork-joins
+/// once a subslice is large enough to be worth the task overhead.
+#[cfg(feature = "rayon")]
+pub mod par {
+    use std::cmp::Ordering;
+    use std::cmp::Ordering::Equal;
+    use std::cmp::min;
+    use std::mem::size_of;
+    use rayon_core::join;
+    use super::{PivotChoice, choose_pivots, dual_pivot_partition, fat_partition, partition_equal,
+                block_partition, break_patterns, maybe_insertion_sort, partial_insertion_sort,
+                heapsort, introsort, log2};
+
+    /// Subslices shorter than this are sorted sequentially to avoid task overhead.
+    const PARALLEL_THRESHOLD: usize = 8 * 1024;
+
+    /// Sorts `v` in parallel using the comparator `compare`.
+    pub fn par_sort_by<T, C>(v: &mut [T], compare: &C)
+        where T: Send + Sync, C: Sync + Fn(&T, &T) -> Ordering
+    {
+        if maybe_insertion_sort(v, compare) { return; }
+        let heapsort_depth = (3 * log2(v.len())) / 2;
+        let bad_allowed = log2(v.len());
+        par_do_introsort(v, compare, 0, heapsort_depth, bad_allowed, None);
+    }
+
+    /// Sorts `v` in parallel.
+    pub fn par_sort<T: Ord + Send + Sync>(v: &mut [T]) {
+        par_sort_by(v, &|a, b| a.cmp(b));
+    }
+
+    fn par_introsort<T, C>(v: &mut [T], compare: &C, rec: u32, heapsort_depth: u32, bad_allowed: u32, pred: Option<&T>)
+        where T: Send + Sync, C: Sync + Fn(&T, &T) -> Ordering
+    {
+        if maybe_insertion_sort(v, compare) { return; }
+        par_do_introsort(v, compare, rec, heapsort_depth, bad_allowed, pred);
+    }
+
+    fn par_do_introsort<T, C>(v: &mut [T], compare: &C, rec: u32, heapsort_depth: u32, bad_allowed: u32, pred: Option<&T>)
+        where T: Send + Sync, C: Sync + Fn(&T, &T) -> Ordering
+    {
+        if rec > heapsort_depth {
+            heapsort(v, compare);
+            return;
+        }
+        if partial_insertion_sort(v, compare) {
+            return;
+        }
+
+        let n = v.len();
+        let (median, choice) = choose_pivots(v, compare);
+
+        if let Some(p) = pred {
+            if unsafe { compare(v.get_unchecked(median), p) } == Equal {
+                let eq = partition_equal(v, median, compare);
+                if n - eq > 1 {
+                    par_introsort(&mut v[eq..], compare, rec + 1, heapsort_depth, bad_allowed, pred);
+                }
+                return;
+            }
+        }
+
+        match choice {
+            PivotChoice::Dual(pivots) => par_dual_pivot_sort(v, pivots, compare, rec, heapsort_depth, bad_allowed, pred),
+            PivotChoice::Single(pivot) => par_single_pivot_sort(v, pivot, compare, rec, heapsort_depth, bad_allowed, pred),
+        }
+    }
+
+    fn par_dual_pivot_sort<T, C>(v: &mut [T], pivots: (usize, usize, usize, usize, usize),
+                                 compare: &C, rec: u32, heapsort_depth: u32, bad_allowed: u32, pred: Option<&T>)
+        where T: Send + Sync, C: Sync + Fn(&T, &T) -> Ordering
+    {
+        let (less, great) = dual_pivot_partition(v, pivots, compare);
+
+        let n = v.len();
+        let left = less - 1;
+        let center = great + 1 - less;
+        let right = n - (great + 2);
+        let bad_allowed = if min(left, min(center, right)) < n / 8 {
+            if bad_allowed == 0 {
+                heapsort(v, compare);
+                return;
+            }
+            break_patterns(&mut v[..less - 1]);
+            break_patterns(&mut v[great + 2..]);
+            bad_allowed - 1
+        } else {
+            bad_allowed
+        };
+
+        // Carve the three parts and their two pivot predecessors into disjoint
+        // mutable slices so the recursive sorts can run on separate threads.
+        let (head, tail) = v.split_at_mut(less);
+        let (left_part, pivot1_slice) = head.split_at_mut(less - 1);
+        let (center_part, rest) = tail.split_at_mut(great + 1 - less);
+        let (pivot2_slice, right_part) = rest.split_at_mut(1);
+        let pivot1 = &pivot1_slice[0];
+        let pivot2 = &pivot2_slice[0];
+
+        if n >= PARALLEL_THRESHOLD {
+            join(
+                || par_introsort(left_part, compare, rec + 1, heapsort_depth, bad_allowed, pred),
+                || join(
+                    || par_introsort(center_part, compare, rec + 1, heapsort_depth, bad_allowed, Some(pivot1)),
+                    || par_introsort(right_part, compare, rec + 1, heapsort_depth, bad_allowed, Some(pivot2)),
+                ),
+            );
+        } else {
+            introsort(left_part, compare, rec + 1, heapsort_depth, bad_allowed, pred);
+            introsort(center_part, compare, rec + 1, heapsort_depth, bad_allowed, Some(pivot1));
+            introsort(right_part, compare, rec + 1, heapsort_depth, bad_allowed, Some(pivot2));
+        }
+    }
+
+    fn par_single_pivot_sort<T, C>(v: &mut [T], pivot: usize, compare: &C, rec: u32, heapsort_depth: u32, bad_allowed: u32, pred: Option<&T>)
+        where T: Send + Sync, C: Sync + Fn(&T, &T) -> Ordering
+    {
+        let n = v.len();
+
+        // Mirror the sequential `single_pivot_sort`: small types use the
+        // branchless two-way `block_partition`, larger types keep the
+        // many-equals `fat_partition`.
+        if size_of::<T>() <= 2 * size_of::<usize>() {
+            let p = block_partition(v, pivot, compare);
+            let l = p;
+            let r = n - p - 1;
+            let bad_allowed = if min(l, r) < n / 8 {
+                if bad_allowed == 0 {
+                    heapsort(v, compare);
+                    return;
+                }
+                if l > 1 { break_patterns(&mut v[..p]); }
+                if r > 1 { break_patterns(&mut v[p + 1..]); }
+                bad_allowed - 1
+            } else {
+                bad_allowed
+            };
+
+            let (head, right_part) = v.split_at_mut(p + 1);
+            let (left_part, pivot_slice) = head.split_at_mut(p);
+            let eq_pred = &pivot_slice[0];
+
+            if n >= PARALLEL_THRESHOLD {
+                join(
+                    || if l > 1 { par_introsort(left_part, compare, rec + 1, heapsort_depth, bad_allowed, pred) },
+                    || if r > 1 { par_introsort(right_part, compare, rec + 1, heapsort_depth, bad_allowed, Some(eq_pred)) },
+                );
+            } else {
+                if l > 1 {
+                    introsort(left_part, compare, rec + 1, heapsort_depth, bad_allowed, pred);
+                }
+                if r > 1 {
+                    introsort(right_part, compare, rec + 1, heapsort_depth, bad_allowed, Some(eq_pred));
+                }
+            }
+            return;
+        }
+
+        let (l, r) = fat_partition(v, pivot, compare);
+        let bad_allowed = if min(l, r) < n / 8 {
+            if bad_allowed == 0 {
+                heapsort(v, compare);
+                return;
+            }
+            if l > 1 { break_patterns(&mut v[..l]); }
+            if r > 1 { break_patterns(&mut v[n - r..]); }
+            bad_allowed - 1
+        } else {
+            bad_allowed
+        };
+
+        let (head, right_part) = v.split_at_mut(n - r);
+        let (left_part, eq) = head.split_at_mut(l);
+        let eq_pred = &eq[eq.len() - 1];
+
+        if n >= PARALLEL_THRESHOLD {
+            join(
+                || if l > 1 { par_introsort(left_part, compare, rec + 1, heapsort_depth, bad_allowed, pred) },
+                || if r > 1 { par_introsort(right_part, compare, rec + 1, heapsort_depth, bad_allowed, Some(eq_pred)) },
+            );
+        } else {
+            if l > 1 {
+                introsort(left_part, compare, rec + 1, heapsort_depth, bad_allowed, pred);
+            }
+            if r > 1 {
+                introsort(right_part, compare, rec + 1, heapsort_depth, bad_allowed, Some(eq_pred));
+            }
+        }
+    }
+}
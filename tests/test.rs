@@ -2,7 +2,7 @@ extern crate quickersort;
 extern crate rand;
 extern crate itertools;
 
-use quickersort::{sort_by, insertion_sort, heapsort};
+use quickersort::{sort_by, sort_by_key, sort_unstable_by_key, sort_by_cached_key, insertion_sort, heapsort};
 use rand::{Rng, weak_rng};
 use itertools::Itertools;
 use std::cmp::Ordering::*;
@@ -66,3 +66,187 @@ fn test_insertion_sort() {
     do_test_sort!(insertion_sort);
 }
 
+// Inputs that exercise the pattern-defeating adaptivity: the partial insertion
+// sort should make sorted/reverse/mostly-sorted slices cheap, and break_patterns
+// keeps adversarial median-killer inputs out of quadratic behavior.
+#[test]
+fn test_adaptive_patterns() {
+    let cmp = |a: &usize, b: &usize| a.cmp(b);
+    let check = |v: &[usize]| assert!(v.windows(2).all(|w| w[0] <= w[1]));
+
+    for &len in &[300usize, 1000, 20_000] {
+        // Already sorted.
+        let mut sorted: Vec<usize> = (0..len).collect();
+        sort_by(&mut sorted[..], &cmp);
+        check(&sorted);
+
+        // Reverse sorted.
+        let mut reverse: Vec<usize> = (0..len).rev().collect();
+        sort_by(&mut reverse[..], &cmp);
+        check(&reverse);
+
+        // Mostly sorted: a sorted run with a few elements perturbed.
+        let mut mostly: Vec<usize> = (0..len).collect();
+        let step = len / 10 + 1;
+        let mut i = 0;
+        while i + 1 < len {
+            mostly.swap(i, i + 1);
+            i += step;
+        }
+        sort_by(&mut mostly[..], &cmp);
+        check(&mostly);
+
+        // Organ-pipe: rises then falls, a classic median-of-k killer shape.
+        let mut organ: Vec<usize> = (0..len).map(|i| i.min(len - 1 - i)).collect();
+        sort_by(&mut organ[..], &cmp);
+        check(&organ);
+    }
+}
+
+// A small type with only O(1) distinct values at large length. This exercises
+// the block-partition route in `single_pivot_sort`, which relies on the
+// equal-key `pred` fast path to stay linear rather than quadratic.
+#[test]
+fn test_few_distinct_values() {
+    let cmp = |a: &usize, b: &usize| a.cmp(b);
+    for &distinct in &[1usize, 2, 3, 5] {
+        let mut v: Vec<usize> = (0..100_000).map(|i| i % distinct).collect();
+        sort_by(&mut v[..], &cmp);
+        assert!(v.windows(2).all(|w| w[0] <= w[1]));
+    }
+
+    // All elements equal.
+    let mut v = vec![7usize; 50_000];
+    sort_by(&mut v[..], &cmp);
+    assert!(v.iter().all(|&x| x == 7));
+}
+
+// Duplicate-dominated inputs for a larger type route through fat_partition and
+// the partition_equal predecessor fast path rather than block_partition.
+#[test]
+fn test_duplicate_dominated_large_type() {
+    // [usize; 4] is bigger than 2 * size_of::<usize>(), so it takes the
+    // fat_partition route in single_pivot_sort.
+    let cmp = |a: &[usize; 4], b: &[usize; 4]| a[0].cmp(&b[0]);
+    for &distinct in &[1usize, 2, 4] {
+        let mut v: Vec<[usize; 4]> = (0..40_000).map(|i| [i % distinct, 0, 0, 0]).collect();
+        sort_by(&mut v[..], &cmp);
+        assert!(v.windows(2).all(|w| w[0][0] <= w[1][0]));
+    }
+}
+
+// Lengths straddling the block-partition leftover-sweep boundary (2 * BLOCK =
+// 256) make sure the transition between the block loop and the branchy
+// remainder sweep is handled correctly.
+#[test]
+fn test_block_partition_boundary() {
+    let cmp = |a: &usize, b: &usize| a.cmp(b);
+    // Few distinct values force the single-pivot / block_partition route; the
+    // lengths straddle the internal 2 * BLOCK = 256 block-loop boundary so both
+    // the block loop and the branchy leftover sweep run.
+    for len in (250usize..=262).chain(505..=520) {
+        let mut v: Vec<usize> = (0..len).map(|i| i % 3).collect();
+        sort_by(&mut v[..], &cmp);
+        assert!(v.windows(2).all(|w| w[0] <= w[1]));
+
+        let mut v2: Vec<usize> = (0..len).rev().map(|i| i % 3).collect();
+        sort_by(&mut v2[..], &cmp);
+        assert!(v2.windows(2).all(|w| w[0] <= w[1]));
+    }
+}
+
+// Asserts that `sorted` is a permutation of `original` and is nondecreasing by
+// the key `f`.
+fn assert_sorted_permutation<T: Clone + Ord, K: Ord, F: Fn(&T) -> K>(original: &[T], sorted: &[T], f: &F) {
+    let mut a: Vec<T> = original.to_vec();
+    let mut b: Vec<T> = sorted.to_vec();
+    a.sort();
+    b.sort();
+    assert!(a == b, "result is not a permutation of the input");
+    assert!(sorted.windows(2).all(|w| f(&w[0]) <= f(&w[1])), "result is not key-nondecreasing");
+}
+
+#[test]
+fn test_sort_by_key() {
+    // Sort by the last decimal digit; the key function collides heavily.
+    let key = |x: &usize| x % 10;
+    for &len in &[0usize, 1, 2, 500, 10_000] {
+        let original: Vec<usize> = (0..len).map(|i| (i * 9301 + 49297) % 233280).collect();
+
+        let mut v = original.clone();
+        sort_by_key(&mut v[..], &key);
+        assert_sorted_permutation(&original, &v, &key);
+
+        let mut v = original.clone();
+        sort_unstable_by_key(&mut v[..], &key);
+        assert_sorted_permutation(&original, &v, &key);
+    }
+}
+
+#[test]
+fn test_sort_by_cached_key() {
+    // The cycle-following permutation in sort_by_cached_key is the subtle spot:
+    // check it is a permutation and key-nondecreasing across empty, single, and
+    // heavily-colliding inputs.
+    let key = |x: &usize| x % 7;
+    for &len in &[0usize, 1, 2, 3, 256, 10_000] {
+        let original: Vec<usize> = (0..len).map(|i| (i * 2654435761) % 1000).collect();
+        let mut v = original.clone();
+        sort_by_cached_key(&mut v[..], &key);
+        assert_sorted_permutation(&original, &v, &key);
+    }
+
+    // All keys identical: every element is its own cycle or one long cycle.
+    let original = vec![42usize; 5000];
+    let mut v = original.clone();
+    sort_by_cached_key(&mut v[..], &|_| 0usize);
+    assert_sorted_permutation(&original, &v, &|_| 0usize);
+
+    // Distinct keys in reverse order, exercising a full permutation.
+    let original: Vec<usize> = (0..1000).rev().collect();
+    let mut v = original.clone();
+    sort_by_cached_key(&mut v[..], &|x: &usize| *x);
+    assert_sorted_permutation(&original, &v, &|x: &usize| *x);
+    assert!(v.windows(2).all(|w| w[0] <= w[1]));
+}
+
+#[cfg(feature = "rayon")]
+mod par_tests {
+    use quickersort::par::{par_sort, par_sort_by};
+
+    // All inputs are well above PARALLEL_THRESHOLD (8192) so the fork-join path
+    // is taken for every input that is not already sorted; an already-sorted
+    // slice is finished by the partial insertion sort before any partitioning.
+    const N: usize = 200_000;
+
+    #[test]
+    fn test_par_sort_random() {
+        let mut v: Vec<usize> = (0..N).map(|i| (i * 2654435761) % 1_000_000).collect();
+        par_sort(&mut v[..]);
+        assert!(v.windows(2).all(|w| w[0] <= w[1]));
+    }
+
+    #[test]
+    fn test_par_sort_duplicate_heavy() {
+        let mut v: Vec<usize> = (0..N).map(|i| i % 4).collect();
+        par_sort(&mut v[..]);
+        assert!(v.windows(2).all(|w| w[0] <= w[1]));
+    }
+
+    #[test]
+    fn test_par_sort_adversarial() {
+        // Already sorted, reverse sorted, and an organ-pipe pattern.
+        let mut sorted: Vec<usize> = (0..N).collect();
+        par_sort(&mut sorted[..]);
+        assert!(sorted.windows(2).all(|w| w[0] <= w[1]));
+
+        let mut reverse: Vec<usize> = (0..N).rev().collect();
+        par_sort(&mut reverse[..]);
+        assert!(reverse.windows(2).all(|w| w[0] <= w[1]));
+
+        let mut organ: Vec<usize> = (0..N).map(|i| i.min(N - 1 - i)).collect();
+        par_sort_by(&mut organ[..], &|a: &usize, b: &usize| a.cmp(b));
+        assert!(organ.windows(2).all(|w| w[0] <= w[1]));
+    }
+}
+